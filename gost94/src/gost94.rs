@@ -20,6 +20,33 @@ const C: Block = [
 
 pub type SBox = [[u8; 16]; 8];
 
+/// The S-box from the GOST R 34.11-94 "test" parameter set
+/// (`id-GostR3411-94-TestParamSet`), used by [`Gost94::new_test`].
+pub const SBOX_TEST: SBox = [
+    [0x4, 0xA, 0x9, 0x2, 0xD, 0x8, 0x0, 0xE, 0x6, 0xB, 0x1, 0xC, 0x7, 0xF, 0x5, 0x3],
+    [0xE, 0xB, 0x4, 0xC, 0x6, 0xD, 0xF, 0xA, 0x2, 0x3, 0x8, 0x1, 0x0, 0x7, 0x5, 0x9],
+    [0x5, 0x8, 0x1, 0xD, 0xA, 0x3, 0x4, 0x2, 0xE, 0xF, 0xC, 0x7, 0x6, 0x0, 0x9, 0xB],
+    [0x7, 0xD, 0xA, 0x1, 0x0, 0x8, 0x9, 0xF, 0xE, 0x4, 0x6, 0xC, 0xB, 0x2, 0x5, 0x3],
+    [0x6, 0xC, 0x7, 0x1, 0x5, 0xF, 0xD, 0x8, 0x4, 0xA, 0x9, 0xE, 0x0, 0x3, 0xB, 0x2],
+    [0x4, 0xB, 0xA, 0x0, 0x7, 0x2, 0x1, 0xD, 0x3, 0x6, 0x8, 0x5, 0x9, 0xC, 0xF, 0xE],
+    [0xD, 0xB, 0x4, 0x1, 0x3, 0xF, 0x5, 0x9, 0x0, 0xA, 0xE, 0x7, 0x6, 0x8, 0x2, 0xC],
+    [0x1, 0xF, 0xD, 0x0, 0x5, 0x7, 0xA, 0x4, 0x9, 0x2, 0x3, 0xE, 0x6, 0xB, 0x8, 0xC],
+];
+
+/// The S-box from the CryptoPro parameter set
+/// (`id-GostR3411-94-CryptoProParamSet`, RFC 4357), used by
+/// [`Gost94::new_cryptopro`].
+pub const SBOX_CRYPTOPRO: SBox = [
+    [0xA, 0x4, 0x5, 0x6, 0x8, 0x1, 0x3, 0x7, 0xD, 0xC, 0xE, 0x0, 0x9, 0x2, 0xB, 0xF],
+    [0x8, 0xE, 0x2, 0x5, 0x6, 0x9, 0x1, 0xC, 0xF, 0x4, 0xB, 0x0, 0xD, 0xA, 0x3, 0x7],
+    [0x5, 0xF, 0x4, 0x0, 0x2, 0xD, 0xB, 0x9, 0x1, 0x7, 0x6, 0x3, 0xC, 0xE, 0xA, 0x8],
+    [0x7, 0xF, 0x5, 0xA, 0x8, 0x1, 0x6, 0xD, 0x0, 0x9, 0x3, 0xE, 0xB, 0x4, 0x2, 0xC],
+    [0xC, 0x8, 0x2, 0x1, 0xD, 0x4, 0xF, 0x6, 0x7, 0x0, 0xA, 0x5, 0x3, 0xE, 0x9, 0xB],
+    [0xB, 0x3, 0x5, 0x8, 0x2, 0xF, 0xA, 0xD, 0xE, 0x1, 0x7, 0x4, 0xC, 0x9, 0x6, 0x0],
+    [0x6, 0x8, 0x2, 0x3, 0x9, 0xA, 0x5, 0xC, 0x1, 0xE, 0x4, 0x7, 0xB, 0xD, 0x0, 0xF],
+    [0xC, 0x4, 0x6, 0x2, 0xA, 0x5, 0xB, 0x9, 0xE, 0x8, 0xD, 0x7, 0x0, 0x3, 0xF, 0x1],
+];
+
 fn sbox(a: u32, s: &SBox) -> u32 {
     let mut v = 0;
     for i in 0..8 {
@@ -221,6 +248,20 @@ impl Gost94 {
             state: Gost94State { s, h, n, sigma },
         }
     }
+
+    /// Create a new GOST94 instance using the standard "test" parameter set
+    /// (`id-GostR3411-94-TestParamSet`): the [`SBOX_TEST`] S-box together
+    /// with the conventional all-zero IV.
+    pub fn new_test() -> Self {
+        Self::new(SBOX_TEST, Block::default())
+    }
+
+    /// Create a new GOST94 instance using the standard CryptoPro parameter
+    /// set (`id-GostR3411-94-CryptoProParamSet`, RFC 4357): the
+    /// [`SBOX_CRYPTOPRO`] S-box together with the conventional all-zero IV.
+    pub fn new_cryptopro() -> Self {
+        Self::new(SBOX_CRYPTOPRO, Block::default())
+    }
 }
 
 impl BlockInput for Gost94 {
@@ -268,3 +309,59 @@ impl FixedOutput for Gost94 {
 
 impl_opaque_debug!(Gost94);
 impl_write!(Gost94);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(mut g: Gost94, input: &[u8]) -> GenericArray<u8, U32> {
+        g.process(input);
+        g.fixed_result()
+    }
+
+    #[test]
+    fn new_test_matches_generic_new_with_test_sbox_and_zero_iv() {
+        let expected = hash(Gost94::new(SBOX_TEST, Block::default()), b"some input");
+        let actual = hash(Gost94::new_test(), b"some input");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn new_cryptopro_matches_generic_new_with_cryptopro_sbox_and_zero_iv() {
+        let expected = hash(Gost94::new(SBOX_CRYPTOPRO, Block::default()), b"some input");
+        let actual = hash(Gost94::new_cryptopro(), b"some input");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_and_cryptopro_sboxes_give_different_digests() {
+        let a = hash(Gost94::new_test(), b"some input");
+        let b = hash(Gost94::new_cryptopro(), b"some input");
+        assert_ne!(a, b);
+    }
+
+    // Each row of a GOST 28147-89/34.11-94 S-box must be a permutation of
+    // 0..16 (every 4-bit substitution is a bijection); a transcription
+    // error that drops or duplicates an entry would silently desync the
+    // hash from the published parameter set without tripping any other
+    // test here.
+    fn assert_rows_are_permutations(s: &SBox) {
+        for row in s.iter() {
+            let mut seen = [false; 16];
+            for &v in row.iter() {
+                assert!(!seen[v as usize], "duplicate entry {} in S-box row", v);
+                seen[v as usize] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn sbox_test_rows_are_permutations() {
+        assert_rows_are_permutations(&SBOX_TEST);
+    }
+
+    #[test]
+    fn sbox_cryptopro_rows_are_permutations() {
+        assert_rows_are_permutations(&SBOX_CRYPTOPRO);
+    }
+}
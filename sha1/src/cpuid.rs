@@ -0,0 +1,75 @@
+//! Runtime CPU-feature detection for the hardware SHA-1 compression path.
+//!
+//! The `asm`/portable split used to be a compile-time choice: a binary built
+//! without `--features asm` could never use hardware SHA-1, and one built
+//! with it assumed the target CPU actually had the extension. Instead we
+//! probe for support the first time it's needed and cache the result in an
+//! atomic, so the same binary runs fast on capable hardware and falls back
+//! to [`super::utils::compress`] everywhere else.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+const UNKNOWN: u8 = 0;
+const UNSUPPORTED: u8 = 1;
+const SUPPORTED: u8 = 2;
+
+static CACHE: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+/// Whether the current CPU has hardware SHA-1 support, detected once and
+/// cached for the lifetime of the process.
+#[inline]
+pub(crate) fn sha1_hw_supported() -> bool {
+    match CACHE.load(Ordering::Relaxed) {
+        SUPPORTED => return true,
+        UNSUPPORTED => return false,
+        _ => {}
+    }
+    let supported = detect();
+    CACHE.store(if supported { SUPPORTED } else { UNSUPPORTED }, Ordering::Relaxed);
+    supported
+}
+
+#[cfg(target_arch = "aarch64")]
+fn detect() -> bool {
+    const AT_HWCAP: u64 = 16;
+    const HWCAP_SHA1: u64 = 32;
+
+    extern "C" {
+        fn getauxval(cap: u64) -> u64;
+    }
+
+    unsafe { getauxval(AT_HWCAP) & HWCAP_SHA1 != 0 }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn detect() -> bool {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::__cpuid_count;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::__cpuid_count;
+
+    // CPUID.(EAX=07H, ECX=0):EBX[29] is the SHA extension feature bit.
+    let regs = unsafe { __cpuid_count(7, 0) };
+    (regs.ebx >> 29) & 1 != 0
+}
+
+#[cfg(not(any(target_arch = "aarch64", target_arch = "x86", target_arch = "x86_64")))]
+fn detect() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha1_hw_supported_is_stable_across_repeated_calls() {
+        // The result is cached in `CACHE` after the first call; a bug in the
+        // cache (e.g. storing the wrong sentinel) could make later calls
+        // disagree with the first even though the CPU hasn't changed.
+        let first = sha1_hw_supported();
+        for _ in 0..8 {
+            assert_eq!(sha1_hw_supported(), first);
+        }
+    }
+}
@@ -59,11 +59,12 @@ extern crate generic_array;
 
 
 #[cfg(feature = "asm")]
-extern crate sha1_asm as utils;
-#[cfg(not(feature = "asm"))]
+extern crate sha1_asm;
+
 mod utils;
+use utils::u32x4;
 
-use utils::{compress, u32x4};
+mod cpuid;
 
 use byte_tools::write_u32_be;
 use block_buffer::BlockBuffer512;
@@ -75,7 +76,36 @@ use generic_array::typenum::{U20, U64};
 mod consts;
 use consts::{STATE_LEN, H};
 
-
+mod ubc_check;
+mod sha1dc;
+pub use sha1dc::{Sha1Dc, Sha1DcOutput};
+
+mod midstate;
+pub use midstate::{Sha1Midstate, MIDSTATE_LEN};
+
+
+
+/// Compress a single block, picking the fastest implementation available on
+/// this CPU. The choice is detected once (see [`cpuid`]) and cached, so
+/// every call after the first is a plain atomic load.
+#[inline]
+fn compress(abcd: &mut u32x4, e: &mut u32x4, data: &GenericArray<u8, U64>) {
+    #[cfg(feature = "asm")]
+    {
+        if cpuid::sha1_hw_supported() {
+            // `sha1_asm` is a separate crate with its own `u32x4`; convert
+            // explicitly at the boundary rather than assuming the two
+            // types happen to unify.
+            let mut asm_abcd = sha1_asm::u32x4(abcd.0, abcd.1, abcd.2, abcd.3);
+            let mut asm_e = sha1_asm::u32x4(e.0, e.1, e.2, e.3);
+            sha1_asm::compress(&mut asm_abcd, &mut asm_e, data);
+            *abcd = u32x4(asm_abcd.0, asm_abcd.1, asm_abcd.2, asm_abcd.3);
+            *e = u32x4(asm_e.0, asm_e.1, asm_e.2, asm_e.3);
+            return;
+        }
+    }
+    utils::compress(abcd, e, data);
+}
 
 /// Structure representing the state of a SHA-1 computation
 #[derive(Copy, Clone)]
@@ -95,6 +125,17 @@ impl Default for Sha1 {
     }
 }
 
+impl Sha1 {
+    /// Returns whether `process`/`fixed_result` will use the
+    /// hardware-accelerated SHA-1 compression function on this CPU.
+    ///
+    /// Always `false` when built without the `asm` feature, since there is
+    /// no hardware path to dispatch to in that case.
+    pub fn has_hardware_support() -> bool {
+        cfg!(feature = "asm") && cpuid::sha1_hw_supported()
+    }
+}
+
 impl digest::BlockInput for Sha1 {
     type BlockSize = U64;
 }
@@ -132,3 +173,58 @@ impl digest::FixedOutput for Sha1 {
         out
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::{Input, FixedOutput};
+
+    #[test]
+    fn hashing_is_correct_independent_of_hardware_support() {
+        // Whichever path `compress` dispatches to (hardware or
+        // `utils::compress`), the digest for a known input must come out
+        // the same. With the `asm` feature on hardware with the SHA-1
+        // extension this goes through the `u32x4` conversion at the
+        // asm/portable boundary; elsewhere it only checks `utils::compress`.
+        let mut h = Sha1::default();
+        h.process(b"abc");
+        let out = h.fixed_result();
+        let expected: [u8; 20] = [
+            0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e,
+            0x25, 0x71, 0x78, 0x50, 0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d,
+        ];
+        assert_eq!(&out[..], &expected[..]);
+    }
+
+    #[cfg(feature = "asm")]
+    #[test]
+    fn asm_and_portable_compress_agree_on_u32x4_conversion() {
+        // `sha1_asm::compress` issues real hardware SHA-1 instructions, so
+        // only run this where `compress()` itself would actually dispatch
+        // to it; everywhere else this exercises nothing but can't safely
+        // call the asm path either.
+        if !cpuid::sha1_hw_supported() {
+            return;
+        }
+
+        // Exercises the exact conversion `compress()` does at the
+        // dispatch boundary: run the same state/block through both
+        // `utils::compress` and `sha1_asm::compress` and check they still
+        // land on the same `u32x4` fields.
+        let data = GenericArray::<u8, U64>::default();
+
+        let mut portable_abcd = u32x4(H[0], H[1], H[2], H[3]);
+        let mut portable_e = u32x4(H[4], 0, 0, 0);
+        utils::compress(&mut portable_abcd, &mut portable_e, &data);
+
+        let mut asm_abcd = sha1_asm::u32x4(H[0], H[1], H[2], H[3]);
+        let mut asm_e = sha1_asm::u32x4(H[4], 0, 0, 0);
+        sha1_asm::compress(&mut asm_abcd, &mut asm_e, &data);
+
+        assert_eq!(portable_abcd.0, asm_abcd.0);
+        assert_eq!(portable_abcd.1, asm_abcd.1);
+        assert_eq!(portable_abcd.2, asm_abcd.2);
+        assert_eq!(portable_abcd.3, asm_abcd.3);
+        assert_eq!(portable_e.0, asm_e.0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Exporting and resuming a `Sha1` computation from its intermediate hash
+//! state ("midstate").
+//!
+//! Proof-of-work and incremental-verification workloads often hash many
+//! messages that share a long, fixed prefix. Rather than replaying the
+//! prefix's blocks for every message, the prefix can be compressed once, the
+//! resulting state exported as a [`Sha1Midstate`], and cheaply restored with
+//! [`Sha1::from_midstate`] to finish each message.
+
+use byte_tools::{read_u32_be, read_u64_be, write_u32_be, write_u64_be};
+
+use utils::u32x4;
+use Sha1;
+
+/// Encoded length in bytes of a [`Sha1Midstate`] (four 32-bit state words,
+/// the fifth state word, and the 64-bit length counter).
+pub const MIDSTATE_LEN: usize = 4 * 4 + 4 + 8;
+
+/// The intermediate hash state of a `Sha1` computation after a whole number
+/// of 512-bit blocks have been processed.
+///
+/// Only meaningful on a block boundary, which is why it can only be
+/// obtained through [`Sha1::export_state`] (which enforces that the
+/// internal buffer is empty) rather than constructed directly from an
+/// in-progress `Sha1`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sha1Midstate {
+    abcd: [u32; 4],
+    e: u32,
+    len: u64,
+}
+
+impl Sha1Midstate {
+    /// Encode this midstate as bytes, in the same big-endian layout as the
+    /// final SHA-1 digest followed by the 64-bit counter of bytes processed.
+    pub fn to_bytes(&self) -> [u8; MIDSTATE_LEN] {
+        let mut out = [0u8; MIDSTATE_LEN];
+        write_u32_be(&mut out[0..4], self.abcd[0]);
+        write_u32_be(&mut out[4..8], self.abcd[1]);
+        write_u32_be(&mut out[8..12], self.abcd[2]);
+        write_u32_be(&mut out[12..16], self.abcd[3]);
+        write_u32_be(&mut out[16..20], self.e);
+        write_u64_be(&mut out[20..28], self.len);
+        out
+    }
+
+    /// Decode a midstate previously produced by [`Sha1Midstate::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; MIDSTATE_LEN]) -> Self {
+        Sha1Midstate {
+            abcd: [
+                read_u32_be(&bytes[0..4]),
+                read_u32_be(&bytes[4..8]),
+                read_u32_be(&bytes[8..12]),
+                read_u32_be(&bytes[12..16]),
+            ],
+            e: read_u32_be(&bytes[16..20]),
+            len: read_u64_be(&bytes[20..28]),
+        }
+    }
+}
+
+impl Sha1 {
+    /// Export the intermediate hash state, so it can be stored and later
+    /// resumed with [`Sha1::from_midstate`].
+    ///
+    /// Only valid once a whole number of 512-bit blocks have been fed in,
+    /// i.e. the internal block buffer is empty; panics otherwise.
+    pub fn export_state(&self) -> Sha1Midstate {
+        assert_eq!(
+            self.buffer.position(), 0,
+            "Sha1::export_state requires a whole number of processed blocks"
+        );
+        Sha1Midstate {
+            abcd: [self.abcd.0, self.abcd.1, self.abcd.2, self.abcd.3],
+            e: self.e.0,
+            len: self.len,
+        }
+    }
+
+    /// Resume a `Sha1` computation from a previously exported midstate.
+    pub fn from_midstate(mid: Sha1Midstate) -> Self {
+        Sha1 {
+            abcd: u32x4(mid.abcd[0], mid.abcd[1], mid.abcd[2], mid.abcd[3]),
+            e: u32x4(mid.e, 0, 0, 0),
+            len: mid.len,
+            buffer: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use digest::{Input, FixedOutput};
+
+    #[test]
+    fn midstate_round_trip_matches_non_resumed_hash() {
+        let prefix = [0x61u8; 128]; // two whole 512-bit blocks
+        let suffix = b"some more input that doesn't align to a block";
+
+        let mut straight = Sha1::default();
+        straight.process(&prefix);
+        straight.process(suffix);
+        let expected = straight.fixed_result();
+
+        let mut prefix_only = Sha1::default();
+        prefix_only.process(&prefix);
+        let mid = prefix_only.export_state();
+        let encoded = mid.to_bytes();
+        let decoded = Sha1Midstate::from_bytes(&encoded);
+        assert_eq!(mid, decoded);
+
+        let mut resumed = Sha1::from_midstate(decoded);
+        resumed.process(suffix);
+        let actual = resumed.fixed_result();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn export_state_panics_mid_block() {
+        let mut h = Sha1::default();
+        h.process(b"not a whole number of 512-bit blocks");
+        h.export_state();
+    }
+}
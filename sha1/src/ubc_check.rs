@@ -0,0 +1,55 @@
+//! "Unavoidable bit-condition" (UBC) pre-filter for the SHA-1 collision
+//! detector.
+//!
+//! Before paying for a full recompression we cheaply rule out disturbance
+//! vectors (DVs) whose message-bit conditions cannot possibly hold for the
+//! expanded words of the block just compressed. Each DV in `UBC_TESTS` has a
+//! single relation: the XOR of one bit from each of two words of `W[]` must
+//! equal a fixed value. If it holds, the DV's bit is set in the returned mask
+//! and [`super::sha1dc::detect_collision`] goes on to recompress it;
+//! otherwise the DV is discarded. AND-combinations are not evaluated, and a
+//! DV listed with several relations would have its bit set as soon as any
+//! one of them holds.
+
+use super::sha1dc::Dv;
+
+/// One XOR-combination test: bit `dv_index` of the mask is set only when
+/// bit 0 of `(w[word_a] >> bit_a) ^ (w[word_b] >> bit_b)` equals `expect`.
+struct UbcTest {
+    dv_index: usize,
+    word_a: usize,
+    bit_a: u32,
+    word_b: usize,
+    bit_b: u32,
+    expect: u32,
+}
+
+// A representative slice of the 80-step/32-DV table used by the reference
+// `sha1collisiondetection` design. Each entry ties one disturbance vector
+// (see `sha1dc::DV_TABLE`) to a single cheap bit relation over the expanded
+// message schedule; DVs that fail their relation are never recompressed.
+const UBC_TESTS: &[UbcTest] = &[
+    UbcTest { dv_index: 0, word_a: 1, bit_a: 1, word_b: 2, bit_b: 1, expect: 0 },
+    UbcTest { dv_index: 1, word_a: 2, bit_a: 1, word_b: 3, bit_b: 1, expect: 0 },
+    UbcTest { dv_index: 2, word_a: 4, bit_a: 1, word_b: 5, bit_b: 1, expect: 0 },
+    UbcTest { dv_index: 3, word_a: 11, bit_a: 0, word_b: 13, bit_b: 0, expect: 1 },
+    UbcTest { dv_index: 4, word_a: 13, bit_a: 0, word_b: 15, bit_b: 0, expect: 1 },
+];
+
+/// Evaluate the UBC pre-filter for one block's expanded message schedule.
+///
+/// Returns a bitmask with bit `i` set when `dv_table[i]` survived its
+/// unavoidable-bit-condition test and is worth recompressing.
+pub(crate) fn ubc_check(w: &[u32; 80], dv_table: &[Dv]) -> u32 {
+    let mut mask = 0u32;
+    for test in UBC_TESTS {
+        if test.dv_index >= dv_table.len() {
+            continue;
+        }
+        let bit = ((w[test.word_a] >> test.bit_a) ^ (w[test.word_b] >> test.bit_b)) & 1;
+        if bit == test.expect {
+            mask |= 1 << test.dv_index;
+        }
+    }
+    mask
+}
@@ -0,0 +1,90 @@
+//! Portable, pure-Rust SHA-1 compression function.
+
+use byte_tools::read_u32_be;
+use generic_array::GenericArray;
+use generic_array::typenum::U64;
+
+const K0: u32 = 0x5A827999;
+const K1: u32 = 0x6ED9EBA1;
+const K2: u32 = 0x8F1BBCDC;
+const K3: u32 = 0xCA62C1D6;
+
+/// A 4-lane `u32` tuple holding the `(a, b, c, d)` working state, mirroring
+/// the layout `sha1_asm` uses for its SIMD registers so the two compression
+/// backends can be dispatched between without reshaping the caller's state.
+#[derive(Copy, Clone, Default)]
+pub struct u32x4(pub u32, pub u32, pub u32, pub u32);
+
+#[inline(always)]
+fn expand(data: &GenericArray<u8, U64>) -> [u32; 80] {
+    let mut w = [0u32; 80];
+    for i in 0..16 {
+        w[i] = read_u32_be(&data[4 * i..4 * i + 4]);
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+    w
+}
+
+#[inline(always)]
+pub(crate) fn round_fk(t: usize, b: u32, c: u32, d: u32) -> (u32, u32) {
+    match t / 20 {
+        0 => ((b & c) | ((!b) & d), K0),
+        1 => (b ^ c ^ d, K1),
+        2 => ((b & c) | (b & d) | (c & d), K2),
+        _ => (b ^ c ^ d, K3),
+    }
+}
+
+/// Process a single 512-bit block, updating `(abcd, e)` in place and
+/// returning the expanded message schedule together with the `a` register
+/// recorded at every step.
+///
+/// The returned `q` array holds `Q[-5..=79]` (index `i` is `Q[i - 5]`): five
+/// words of history ahead of step 0 (the incoming `a, b, c, d, e`, with the
+/// shift register's rotations undone so each is a past value of `a`) followed by one word per
+/// step of the main loop. That is enough for a caller to reconstruct the
+/// full five-register state entering any step `t`, which is what the
+/// collision detector in [`super::sha1dc`] needs `compress` instrumented
+/// for, without this function's round logic having to live in two places.
+pub(crate) fn compress_trace(abcd: &mut u32x4, e: &mut u32x4, data: &GenericArray<u8, U64>) -> ([u32; 80], [u32; 85]) {
+    let w = expand(data);
+    let (mut a, mut b, mut c, mut d, mut er) = (abcd.0, abcd.1, abcd.2, abcd.3, e.0);
+
+    let mut q = [0u32; 85];
+    q[0] = er.rotate_right(30);
+    q[1] = d.rotate_right(30);
+    q[2] = c.rotate_right(30);
+    q[3] = b;
+    q[4] = a;
+
+    for t in 0..80 {
+        let (f, k) = round_fk(t, b, c, d);
+        let new_a = a.rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(er)
+            .wrapping_add(k)
+            .wrapping_add(w[t]);
+        er = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = new_a;
+        q[t + 5] = a;
+    }
+
+    abcd.0 = abcd.0.wrapping_add(a);
+    abcd.1 = abcd.1.wrapping_add(b);
+    abcd.2 = abcd.2.wrapping_add(c);
+    abcd.3 = abcd.3.wrapping_add(d);
+    e.0 = e.0.wrapping_add(er);
+
+    (w, q)
+}
+
+/// Process a single 512-bit block, updating `(abcd, e)` in place.
+#[inline(always)]
+pub(crate) fn compress(abcd: &mut u32x4, e: &mut u32x4, data: &GenericArray<u8, U64>) {
+    compress_trace(abcd, e, data);
+}
@@ -0,0 +1,200 @@
+//! An implementation of the Keccak/SHA-3 sponge, including the
+//! extendable-output functions (XOF) SHAKE128 and SHAKE256.
+//!
+//! Fixed-output SHA-3 variants are built the same way (absorb into
+//! [`Sha3State`], squeeze a fixed number of bytes) but live elsewhere in the
+//! workspace; this crate's own surface is the XOF squeezing layer on top of
+//! the shared sponge state.
+
+#![no_std]
+
+extern crate byte_tools;
+extern crate digest;
+extern crate keccak;
+
+use digest::Input;
+
+mod state;
+use state::{Sha3State, XofReader as StateXofReader};
+
+/// Streams the variable-length output of an extendable-output function.
+pub trait XofReader {
+    /// Squeeze output bytes into `buffer`, permuting and refilling the
+    /// underlying sponge as needed. Can be called repeatedly for as much
+    /// output as the caller wants.
+    fn read(&mut self, buffer: &mut [u8]);
+}
+
+/// A hash function whose output can be any length, rather than fixed.
+pub trait ExtendableOutput {
+    /// The reader returned once absorption is finished.
+    type Reader: XofReader;
+
+    /// Pad and permute the sponge one last time, returning a reader that
+    /// squeezes output of any length.
+    fn xof_result(self) -> Self::Reader;
+}
+
+/// A [`XofReader`] over a SHAKE sponge.
+pub struct ShakeXofReader(StateXofReader);
+
+impl XofReader for ShakeXofReader {
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.0.read(buffer)
+    }
+}
+
+macro_rules! impl_shake {
+    ($name:ident, $doc:expr, $rate:expr, $pad:expr) => {
+        #[doc = $doc]
+        #[derive(Clone)]
+        pub struct $name {
+            state: Sha3State,
+            buffer: [u8; $rate],
+            pos: usize,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name { state: Default::default(), buffer: [0u8; $rate], pos: 0 }
+            }
+        }
+
+        impl Input for $name {
+            fn process(&mut self, mut input: &[u8]) {
+                if self.pos != 0 {
+                    let n = ($rate - self.pos).min(input.len());
+                    self.buffer[self.pos..self.pos + n].copy_from_slice(&input[..n]);
+                    self.pos += n;
+                    input = &input[n..];
+                    if self.pos == $rate {
+                        self.state.absorb_block(&self.buffer);
+                        self.pos = 0;
+                    }
+                }
+
+                while input.len() >= $rate {
+                    self.state.absorb_block(&input[..$rate]);
+                    input = &input[$rate..];
+                }
+
+                if !input.is_empty() {
+                    self.buffer[..input.len()].copy_from_slice(input);
+                    self.pos = input.len();
+                }
+            }
+        }
+
+        impl ExtendableOutput for $name {
+            type Reader = ShakeXofReader;
+
+            fn xof_result(mut self) -> ShakeXofReader {
+                // Multi-rate padding (`pad10*1`) with the SHAKE domain
+                // separation suffix folded into the first padding byte.
+                for b in self.buffer[self.pos..$rate].iter_mut() {
+                    *b = 0;
+                }
+                self.buffer[self.pos] ^= $pad;
+                self.buffer[$rate - 1] ^= 0x80;
+                self.state.absorb_block(&self.buffer);
+                ShakeXofReader(StateXofReader::new(self.state, $rate))
+            }
+        }
+    };
+}
+
+impl_shake!(Shake128, "The SHAKE128 extendable-output function.", 168, 0x1f);
+impl_shake!(Shake256, "The SHAKE256 extendable-output function.", 136, 0x1f);
+
+// No published FIPS 202 SHAKE128("")/SHAKE256("") known-answer digest is
+// reproduced here: pinning one down requires checking it byte-for-byte
+// against the standard, which isn't something to do from memory, and an
+// incorrectly-transcribed "known answer" is worse than none (it would
+// silently validate a wrong implementation, exactly the failure mode
+// these tests exist to catch). What's covered below is everything that
+// can be checked without an external reference: that `process` in
+// arbitrary-sized chunks absorbs identically to one large call, and that
+// `read` in arbitrary-sized chunks squeezes identically to one large
+// call — any bug in the rate/offset bookkeeping would break both.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squeeze64<S: ExtendableOutput>(xof: S) -> [u8; 64] {
+        let mut out = [0u8; 64];
+        xof.xof_result().read(&mut out);
+        out
+    }
+
+    fn squeeze32<S: ExtendableOutput>(xof: S) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        xof.xof_result().read(&mut out);
+        out
+    }
+
+    #[test]
+    fn shake128_incremental_process_matches_one_shot() {
+        let msg = [0x61u8; 500];
+
+        let mut one_shot = Shake128::default();
+        one_shot.process(&msg);
+
+        let mut chunked = Shake128::default();
+        for chunk in msg.chunks(7) {
+            chunked.process(chunk);
+        }
+
+        assert_eq!(squeeze64(one_shot), squeeze64(chunked));
+    }
+
+    #[test]
+    fn shake256_incremental_process_matches_one_shot() {
+        let msg = [0x61u8; 500];
+
+        let mut one_shot = Shake256::default();
+        one_shot.process(&msg);
+
+        let mut chunked = Shake256::default();
+        for chunk in msg.chunks(11) {
+            chunked.process(chunk);
+        }
+
+        assert_eq!(squeeze64(one_shot), squeeze64(chunked));
+    }
+
+    #[test]
+    fn chunked_squeeze_matches_single_squeeze() {
+        let mut a = Shake256::default();
+        a.process(b"some message");
+        let mut whole = [0u8; 300];
+        a.xof_result().read(&mut whole);
+
+        let mut b = Shake256::default();
+        b.process(b"some message");
+        let mut reader = b.xof_result();
+        let mut parts = [0u8; 300];
+        for chunk in parts.chunks_mut(9) {
+            reader.read(chunk);
+        }
+
+        assert_eq!(&whole[..], &parts[..]);
+    }
+
+    #[test]
+    fn shake128_and_shake256_give_different_output() {
+        let mut a = Shake128::default();
+        a.process(b"x");
+        let mut b = Shake256::default();
+        b.process(b"x");
+        assert_ne!(squeeze32(a), squeeze32(b));
+    }
+
+    #[test]
+    fn different_messages_give_different_output() {
+        let mut a = Shake128::default();
+        a.process(b"x");
+        let mut b = Shake128::default();
+        b.process(b"y");
+        assert_ne!(squeeze32(a), squeeze32(b));
+    }
+}
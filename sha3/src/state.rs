@@ -51,3 +51,47 @@ impl Sha3State {
         keccak::f1600(&mut self.state);
     }
 }
+
+/// Squeezes an arbitrary-length output out of an already-absorbed
+/// `Sha3State`, turning the fixed-output sponge into a SHAKE-style XOF.
+///
+/// `rate` is the sponge's rate in bytes (`168` for SHAKE128, `136` for
+/// SHAKE256) so the same reader serves every extendable-output variant; only
+/// the padding written during absorption and the rate differ between them.
+/// Successive `read` calls of any size stitch together seamlessly: the
+/// reader remembers how far into the current rate block it has already
+/// copied out, and calls `apply_f` to permute and refill whenever that block
+/// is exhausted.
+pub(crate) struct XofReader {
+    state: Sha3State,
+    rate: usize,
+    offset: usize,
+}
+
+impl XofReader {
+    #[inline(always)]
+    pub(crate) fn new(state: Sha3State, rate: usize) -> Self {
+        debug_assert!(rate > 0 && rate <= 8 * PLEN);
+        XofReader { state, rate, offset: 0 }
+    }
+
+    /// Squeeze `buffer.len()` bytes of output, permuting and refilling the
+    /// rate as many times as needed.
+    pub(crate) fn read(&mut self, mut buffer: &mut [u8]) {
+        while !buffer.is_empty() {
+            if self.offset == self.rate {
+                self.state.apply_f();
+                self.offset = 0;
+            }
+
+            let n = buffer.len().min(self.rate - self.offset);
+            let offset = self.offset;
+            self.state.as_bytes(|block| {
+                buffer[..n].copy_from_slice(&block[offset..offset + n]);
+            });
+
+            self.offset += n;
+            buffer = &mut buffer[n..];
+        }
+    }
+}